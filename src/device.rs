@@ -0,0 +1,106 @@
+use roxmltree::Document;
+use ssdp_client::URN;
+
+use crate::{find_in_xml, find_root, service::Service, Error, HttpResponseExt, Result};
+
+/// A UPnP root device, along with the services it exposes.
+///
+/// Obtained by [`discover`](crate::discover)ing devices on the network, or resolving a
+/// device description URL directly with [`DeviceSpec::from_url`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceSpec {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support"))]
+    url: http::Uri,
+    device_type: String,
+    friendly_name: String,
+    manufacturer: String,
+    model_name: String,
+    udn: String,
+    services: Vec<Service>,
+}
+
+/// A discovered UPnP device.
+///
+/// This is currently a plain alias for [`DeviceSpec`]; it exists as a separate name so
+/// that callers don't have to think about the distinction between "the device
+/// description we parsed" and "the device we're talking to".
+pub type Device = DeviceSpec;
+
+impl DeviceSpec {
+    /// Fetch and parse the device description XML at `url`.
+    pub async fn from_url(url: http::Uri) -> Result<Self> {
+        let body = isahc::get_async(&url)
+            .await?
+            .err_if_not_200()?
+            .text()
+            .await?;
+
+        let document = Document::parse(&body)?;
+        let root = find_root(&document, "device", "root")?;
+
+        let (device_type, friendly_name, manufacturer, model_name, udn, service_list) = find_in_xml!(
+            root => deviceType, friendlyName, manufacturer, modelName, UDN, serviceList
+        );
+
+        let mut services = Vec::new();
+        for service in service_list
+            .children()
+            .filter(|n| n.has_tag_name("service"))
+        {
+            services.push(Service::from_xml(&url, service)?);
+        }
+
+        Ok(DeviceSpec {
+            url,
+            device_type: device_type.text().unwrap_or_default().to_string(),
+            friendly_name: friendly_name.text().unwrap_or_default().to_string(),
+            manufacturer: manufacturer.text().unwrap_or_default().to_string(),
+            model_name: model_name.text().unwrap_or_default().to_string(),
+            udn: udn.text().unwrap_or_default().to_string(),
+            services,
+        })
+    }
+
+    /// The device description URL this device was resolved from.
+    pub fn url(&self) -> &http::Uri {
+        &self.url
+    }
+
+    /// The UPnP device type, e.g. `urn:schemas-upnp-org:device:MediaRenderer:1`.
+    pub fn device_type(&self) -> &str {
+        &self.device_type
+    }
+
+    /// The human-readable name of this device.
+    pub fn friendly_name(&self) -> &str {
+        &self.friendly_name
+    }
+
+    /// The device manufacturer.
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    /// The model name.
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    /// The device's unique device name, e.g. `uuid:...`.
+    pub fn udn(&self) -> &str {
+        &self.udn
+    }
+
+    /// All services this device exposes.
+    pub fn services(&self) -> &[Service] {
+        &self.services
+    }
+
+    /// Find the first service matching `service_type`.
+    pub fn find_service(&self, service_type: &URN) -> Option<&Service> {
+        self.services
+            .iter()
+            .find(|service| service.service_type() == service_type)
+    }
+}
@@ -0,0 +1,134 @@
+//! DIDL-Lite metadata parsing.
+//!
+//! Media-renderer and media-server services (`AVTransport`'s `CurrentURIMetaData`,
+//! `ContentDirectory` browse results, ...) return track and item metadata as an embedded
+//! `<DIDL-Lite>` XML document rather than plain SOAP arguments. This module decodes that
+//! document into typed structs instead of leaving callers to re-parse XML by hand.
+
+use std::time::Duration;
+
+use roxmltree::Node;
+
+use crate::{find_in_xml, find_root, parse_node_text, Error, Result};
+
+/// A single item or container inside a `<DIDL-Lite>` document, e.g. a track returned by
+/// `ContentDirectory::Browse` or the metadata attached to `AVTransport`'s current URI.
+#[derive(Debug, Clone)]
+pub struct DidlObject {
+    /// `dc:title`.
+    pub title: String,
+    /// `upnp:class`, e.g. `object.item.audioItem.musicTrack`.
+    pub class: String,
+    /// `upnp:artist`, if present.
+    pub artist: Option<String>,
+    /// `upnp:album`, if present.
+    pub album: Option<String>,
+    /// `dc:creator`, if present.
+    pub creator: Option<String>,
+    /// The resource this object points to, parsed from its `<res>` element, if any.
+    pub resource: Option<DidlResource>,
+}
+
+/// The `<res>` element of a [`DidlObject`]: the URI media data can be fetched from, and
+/// how long it plays for.
+#[derive(Debug, Clone)]
+pub struct DidlResource {
+    /// The resource's URI, e.g. an HTTP stream URL.
+    pub uri: String,
+    /// The resource's `duration` attribute, in the `H:MM:SS` or `H:MM:SS.F` form, parsed
+    /// into a [`Duration`](std::time::Duration).
+    pub duration: Option<Duration>,
+}
+
+/// Parses a `<DIDL-Lite>` document into its `item`/`container` objects.
+pub fn parse_didl(xml: &str) -> Result<Vec<DidlObject>> {
+    let document = roxmltree::Document::parse(xml)?;
+    let root = find_root(&document, "DIDL-Lite", "DIDL-Lite")?;
+
+    root.children()
+        .filter(|n| n.has_tag_name("item") || n.has_tag_name("container"))
+        .map(parse_object)
+        .collect()
+}
+
+fn parse_object(node: Node<'_, '_>) -> Result<DidlObject> {
+    let (title, class) = find_in_xml!(node => title, class);
+
+    let title = parse_node_text(title)?;
+    let class = parse_node_text(class)?;
+
+    let artist = find_child_text(node, "artist");
+    let album = find_child_text(node, "album");
+    let creator = find_child_text(node, "creator");
+
+    let resource = node
+        .children()
+        .find(|n| n.has_tag_name("res"))
+        .map(parse_resource)
+        .transpose()?;
+
+    Ok(DidlObject {
+        title,
+        class,
+        artist,
+        album,
+        creator,
+        resource,
+    })
+}
+
+fn parse_resource(node: Node<'_, '_>) -> Result<DidlResource> {
+    let uri = parse_node_text(node)?;
+    let duration = node
+        .attribute("duration")
+        .map(parse_didl_duration)
+        .transpose()?;
+
+    Ok(DidlResource { uri, duration })
+}
+
+fn find_child_text(node: Node<'_, '_>, tag_name: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.has_tag_name(tag_name))
+        .and_then(|n| n.text())
+        .map(str::to_string)
+}
+
+/// Parses a `res@duration` value in the `H:MM:SS` or `H:MM:SS.F` form into a [`Duration`].
+fn parse_didl_duration(text: &str) -> Result<Duration> {
+    let (text, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) => (whole, format!("0.{}", fraction).parse().unwrap_or(0.0)),
+        None => (text, 0.0),
+    };
+
+    let mut parts = text.rsplit(':');
+    let seconds: u64 = parts
+        .next()
+        .ok_or_else(|| Error::invalid_response(InvalidDuration(text.to_string())))?
+        .parse()
+        .map_err(Error::invalid_response)?;
+    let minutes: u64 = parts
+        .next()
+        .unwrap_or("0")
+        .parse()
+        .map_err(Error::invalid_response)?;
+    let hours: u64 = parts
+        .next()
+        .unwrap_or("0")
+        .parse()
+        .map_err(Error::invalid_response)?;
+
+    let total_seconds = hours * 3600 + minutes * 60 + seconds;
+    Ok(Duration::from_secs(total_seconds) + Duration::from_secs_f64(fraction))
+}
+
+#[derive(Debug)]
+struct InvalidDuration(String);
+
+impl std::fmt::Display for InvalidDuration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is not a valid H:MM:SS duration", self.0)
+    }
+}
+
+impl std::error::Error for InvalidDuration {}
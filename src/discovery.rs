@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use futures::{Stream, StreamExt, TryStreamExt};
+use ssdp_client::{SearchTarget, URN};
+
+use crate::{device::DeviceSpec, Device, Error, Result};
+
+/// Discover devices on the network advertising `search_target`, waiting up to `timeout`
+/// for responses.
+///
+/// Each discovered device's description XML is fetched and parsed as it is announced, so
+/// the returned stream yields devices as they respond rather than all at once.
+pub async fn discover(
+    search_target: &SearchTarget,
+    timeout: Duration,
+) -> Result<impl Stream<Item = Result<Device>>> {
+    let responses = ssdp_client::search(search_target, timeout, 3).await?;
+
+    Ok(responses
+        .map_err(Error::from)
+        .and_then(|response| async move {
+            let location = response.location().parse()?;
+            DeviceSpec::from_url(location).await
+        }))
+}
+
+/// Discover every root device on the network, regardless of what services it advertises.
+///
+/// This searches `ssdp::all` rather than a specific URN, so it resolves every responding
+/// device's full service list, not just devices implementing a particular service.
+pub async fn discover_all(timeout: Duration) -> Result<impl Stream<Item = Result<Device>>> {
+    discover(&SearchTarget::All, timeout).await
+}
+
+/// Find the first device advertising `urn` whose friendly name matches `name`.
+///
+/// The match is case-insensitive; `name` may be the device's full friendly name or just a
+/// substring of it. Devices that error out while being resolved (e.g. an unreachable or
+/// malformed description) are skipped rather than aborting the search, since a real LAN
+/// search typically gets responses from several unrelated devices. Short-circuits as soon
+/// as a match is found instead of waiting out the full discovery `timeout`.
+pub async fn find_device_by_name(
+    urn: &URN,
+    name: &str,
+    timeout: Duration,
+) -> Result<Option<Device>> {
+    let devices = discover(&urn.clone().into(), timeout).await?;
+    Ok(find_first_matching(devices, name).await)
+}
+
+async fn find_first_matching(
+    devices: impl Stream<Item = Result<Device>>,
+    name: &str,
+) -> Option<Device> {
+    pin_utils::pin_mut!(devices);
+
+    while let Some(device) = devices.next().await {
+        let device = match device {
+            Ok(device) => device,
+            Err(_) => continue,
+        };
+
+        if device
+            .friendly_name()
+            .to_lowercase()
+            .contains(&name.to_lowercase())
+        {
+            return Some(device);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+/// The errors that can occur when discovering devices, reading service descriptions
+/// or invoking actions on them.
+#[derive(Debug)]
+pub enum Error {
+    /// An IO error occurred.
+    IOError(std::io::Error),
+    /// An error occurred while performing the HTTP request.
+    HttpError(isahc::Error),
+    /// The HTTP request completed, but the response had a non-200 status code.
+    HttpErrorCode(http::StatusCode),
+    /// The url could not be parsed.
+    InvalidUri(http::uri::InvalidUri),
+    /// An error occurred while sending or parsing an SSDP message.
+    SSDPError(ssdp_client::Error),
+    /// The XML returned by the device was not well-formed.
+    InvalidXML(roxmltree::Error),
+    /// The XML was well-formed, but was missing an element this crate expects to be there.
+    ///
+    /// The first field is the element the missing element was searched in, the second field
+    /// is the name of the missing element.
+    XMLMissingElement(String, String),
+    /// A value could not be parsed out of the response.
+    InvalidResponse(Box<dyn std::error::Error + Send + Sync + 'static>),
+    /// The requested action does not exist on this service's SCPD.
+    ActionNotFound(String),
+    /// A supplied argument is not one of the action's in-arguments.
+    ArgumentNotFound(String, String),
+    /// A required in-argument was not supplied.
+    MissingArgument(String, String),
+    /// An argument's value did not satisfy the data type or allowed value list declared
+    /// for it in the SCPD.
+    InvalidArgument(String, String),
+}
+
+impl Error {
+    pub(crate) fn invalid_response<E>(err: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Error::InvalidResponse(Box::new(err))
+    }
+
+    /// Like [`Error::invalid_response`], for call sites that have a plain string reason
+    /// rather than an underlying error to wrap.
+    pub(crate) fn invalid_response_msg(msg: impl Into<String>) -> Self {
+        Error::invalid_response(Msg(msg.into()))
+    }
+}
+
+/// A plain string reason, wrapped so it can be boxed as a `std::error::Error` inside
+/// [`Error::InvalidResponse`].
+#[derive(Debug)]
+struct Msg(String);
+
+impl fmt::Display for Msg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Msg {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IOError(e) => write!(f, "io error: {}", e),
+            Error::HttpError(e) => write!(f, "http error: {}", e),
+            Error::HttpErrorCode(code) => write!(f, "http request failed with status {}", code),
+            Error::InvalidUri(e) => write!(f, "invalid uri: {}", e),
+            Error::SSDPError(e) => write!(f, "ssdp error: {}", e),
+            Error::InvalidXML(e) => write!(f, "invalid xml: {}", e),
+            Error::XMLMissingElement(node, element) => {
+                write!(f, "`{}` is missing child/attribute `{}`", node, element)
+            }
+            Error::InvalidResponse(e) => write!(f, "invalid response: {}", e),
+            Error::ActionNotFound(action) => write!(f, "no such action `{}`", action),
+            Error::ArgumentNotFound(action, arg) => {
+                write!(f, "action `{}` has no argument `{}`", action, arg)
+            }
+            Error::MissingArgument(action, arg) => {
+                write!(f, "action `{}` requires argument `{}`", action, arg)
+            }
+            Error::InvalidArgument(arg, reason) => {
+                write!(f, "argument `{}` is invalid: {}", arg, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::IOError(e) => Some(e),
+            Error::HttpError(e) => Some(e),
+            Error::InvalidUri(e) => Some(e),
+            Error::SSDPError(e) => Some(e),
+            Error::InvalidXML(e) => Some(e),
+            Error::InvalidResponse(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IOError(e)
+    }
+}
+impl From<isahc::Error> for Error {
+    fn from(e: isahc::Error) -> Self {
+        Error::HttpError(e)
+    }
+}
+impl From<http::uri::InvalidUri> for Error {
+    fn from(e: http::uri::InvalidUri) -> Self {
+        Error::InvalidUri(e)
+    }
+}
+impl From<ssdp_client::Error> for Error {
+    fn from(e: ssdp_client::Error) -> Self {
+        Error::SSDPError(e)
+    }
+}
+impl From<roxmltree::Error> for Error {
+    fn from(e: roxmltree::Error) -> Self {
+        Error::InvalidXML(e)
+    }
+}
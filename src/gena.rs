@@ -0,0 +1,405 @@
+//! GENA (General Event Notification Architecture) event subscriptions.
+//!
+//! Every UPnP service advertises an `eventSubURL` that control points can `SUBSCRIBE` to
+//! in order to be notified whenever the service's state variables change. This module
+//! drives that exchange: it sends the initial `SUBSCRIBE`, runs a small local HTTP
+//! listener that the device `NOTIFY`s, periodically `RENEW`s the subscription, and sends
+//! `UNSUBSCRIBE` once the caller is no longer interested.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::UdpSocket;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use async_std::io::prelude::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use futures::channel::{mpsc, oneshot};
+use futures::future::{select, Either};
+use futures::stream::Stream;
+use roxmltree::Document;
+
+use crate::{find_root, service::Service, Error, Result};
+
+/// One batch of state variables a device reported as changed, keyed by variable name.
+///
+/// `SEQ 0`, the first batch a subscriber receives, is the full current state of every
+/// evented variable; subsequent batches only contain the variables that changed.
+pub type StateVars = HashMap<String, String>;
+
+/// A live GENA subscription.
+///
+/// Yields a [`StateVars`] batch every time the device sends a `NOTIFY`. Dropping the
+/// stream sends `UNSUBSCRIBE` and stops the local listener.
+#[derive(Debug)]
+pub struct Subscription {
+    event_sub_endpoint: http::Uri,
+    /// The current SID, shared with `renew_loop` so it can update it after a successful
+    /// `RENEW` (a device is free to hand back a different SID) and `Drop` always
+    /// `UNSUBSCRIBE`s with the SID that's actually live.
+    sid: Arc<Mutex<String>>,
+    receiver: mpsc::UnboundedReceiver<Result<StateVars>>,
+    /// Fired in `Drop` to stop `notify_loop`'s accept loop; `None` once sent.
+    cancel_notify_loop: Option<oneshot::Sender<()>>,
+    /// Fired in `Drop` to stop `renew_loop`'s sleep/renew cycle; `None` once sent.
+    cancel_renew_loop: Option<oneshot::Sender<()>>,
+}
+
+impl Stream for Subscription {
+    type Item = Result<StateVars>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel_notify_loop.take() {
+            let _ = cancel.send(());
+        }
+        if let Some(cancel) = self.cancel_renew_loop.take() {
+            let _ = cancel.send(());
+        }
+
+        let event_sub_endpoint = self.event_sub_endpoint.clone();
+        let sid = Arc::clone(&self.sid);
+        async_std::task::spawn(async move {
+            let sid = sid.lock().unwrap().clone();
+            let _ = send_unsubscribe(&event_sub_endpoint, &sid).await;
+        });
+    }
+}
+
+pub(crate) async fn subscribe(
+    service: &Service,
+    device_url: &http::Uri,
+    timeout: Duration,
+) -> Result<Subscription> {
+    let listener = TcpListener::bind("0.0.0.0:0").await?;
+    let port = listener.local_addr()?.port();
+    let our_ip = local_ip_towards(device_url)?;
+    let token = fresh_token();
+
+    let callback = format!("<http://{}:{}/{}>", our_ip, port, token);
+    let event_sub_endpoint = service.event_sub_endpoint().clone();
+
+    let (sid, granted_timeout) = send_subscribe(&event_sub_endpoint, &callback, timeout).await?;
+    let sid = Arc::new(Mutex::new(sid));
+
+    let (sender, receiver) = mpsc::unbounded();
+    let (cancel_notify_tx, cancel_notify_rx) = oneshot::channel();
+    let (cancel_renew_tx, cancel_renew_rx) = oneshot::channel();
+
+    async_std::task::spawn(notify_loop(
+        listener,
+        token,
+        sender.clone(),
+        cancel_notify_rx,
+    ));
+    async_std::task::spawn(renew_loop(
+        event_sub_endpoint.clone(),
+        Arc::clone(&sid),
+        granted_timeout,
+        sender,
+        cancel_renew_rx,
+    ));
+
+    Ok(Subscription {
+        event_sub_endpoint,
+        sid,
+        receiver,
+        cancel_notify_loop: Some(cancel_notify_tx),
+        cancel_renew_loop: Some(cancel_renew_tx),
+    })
+}
+
+async fn send_subscribe(
+    event_sub_endpoint: &http::Uri,
+    callback: &str,
+    timeout: Duration,
+) -> Result<(String, Duration)> {
+    let request = isahc::Request::builder()
+        .method("SUBSCRIBE")
+        .uri(event_sub_endpoint)
+        .header("CALLBACK", callback)
+        .header("NT", "upnp:event")
+        .header("TIMEOUT", format!("Second-{}", timeout.as_secs()))
+        .body(())
+        .map_err(Error::invalid_response)?;
+
+    let response = isahc::send_async(request).await?;
+    if response.status() != 200 {
+        return Err(Error::HttpErrorCode(response.status()));
+    }
+
+    parse_subscribe_response(&response, timeout)
+}
+
+async fn send_renew(
+    event_sub_endpoint: &http::Uri,
+    sid: &str,
+    timeout: Duration,
+) -> Result<(String, Duration)> {
+    let request = isahc::Request::builder()
+        .method("SUBSCRIBE")
+        .uri(event_sub_endpoint)
+        .header("SID", sid)
+        .header("TIMEOUT", format!("Second-{}", timeout.as_secs()))
+        .body(())
+        .map_err(Error::invalid_response)?;
+
+    let response = isahc::send_async(request).await?;
+    if response.status() != 200 {
+        return Err(Error::HttpErrorCode(response.status()));
+    }
+
+    parse_subscribe_response(&response, timeout)
+}
+
+async fn send_unsubscribe(event_sub_endpoint: &http::Uri, sid: &str) -> Result<()> {
+    let request = isahc::Request::builder()
+        .method("UNSUBSCRIBE")
+        .uri(event_sub_endpoint)
+        .header("SID", sid)
+        .body(())
+        .map_err(Error::invalid_response)?;
+
+    isahc::send_async(request).await?;
+    Ok(())
+}
+
+fn parse_subscribe_response(
+    response: &http::Response<isahc::Body>,
+    requested: Duration,
+) -> Result<(String, Duration)> {
+    let sid = response
+        .headers()
+        .get("SID")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| Error::invalid_response_msg("SUBSCRIBE response is missing SID header"))?
+        .to_string();
+
+    let granted = response
+        .headers()
+        .get("TIMEOUT")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Second-"))
+        .and_then(|secs| secs.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(requested);
+
+    Ok((sid, granted))
+}
+
+/// Auto-renews the subscription a bit before its granted timeout expires, for as long as
+/// anyone is still listening on `sender`.
+///
+/// Races the sleep against `cancel`, which `Subscription::drop` fires, so this task exits
+/// as soon as the caller loses interest instead of lingering for up to the granted
+/// timeout. `sid` is shared with the `Subscription` so a SID a `RENEW` hands back is
+/// immediately visible to the `UNSUBSCRIBE` `Drop` sends.
+async fn renew_loop(
+    event_sub_endpoint: http::Uri,
+    sid: Arc<Mutex<String>>,
+    mut granted_timeout: Duration,
+    sender: mpsc::UnboundedSender<Result<StateVars>>,
+    mut cancel: oneshot::Receiver<()>,
+) {
+    loop {
+        let sleep_for = granted_timeout.mul_f64(0.8);
+        let sleep = async_std::task::sleep(sleep_for);
+        pin_utils::pin_mut!(sleep);
+
+        match select(sleep, &mut cancel).await {
+            Either::Left(_) => {}
+            Either::Right(_) => return,
+        }
+
+        if sender.is_closed() {
+            return;
+        }
+
+        let current_sid = sid.lock().unwrap().clone();
+        match send_renew(&event_sub_endpoint, &current_sid, granted_timeout).await {
+            Ok((new_sid, new_timeout)) => {
+                *sid.lock().unwrap() = new_sid;
+                granted_timeout = new_timeout;
+            }
+            Err(e) => {
+                let _ = sender.unbounded_send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+/// Accepts `NOTIFY` requests on `listener` for as long as anyone is still listening on
+/// `sender`, parsing each one into a [`StateVars`] batch.
+///
+/// Races every `accept` against `cancel`, which `Subscription::drop` fires, so the
+/// listening socket and this task are torn down as soon as the caller loses interest
+/// instead of blocking forever on a device that has stopped sending `NOTIFY`s.
+async fn notify_loop(
+    listener: TcpListener,
+    token: String,
+    sender: mpsc::UnboundedSender<Result<StateVars>>,
+    mut cancel: oneshot::Receiver<()>,
+) {
+    // SEQ is monotonically increasing per subscription; devices may resend a NOTIFY
+    // they didn't get a timely ack for, so track the last SEQ we accepted and drop
+    // duplicate/out-of-order ones.
+    let mut last_seq: Option<u64> = None;
+
+    loop {
+        let accept = listener.accept();
+        pin_utils::pin_mut!(accept);
+
+        let (stream, _) = match select(accept, &mut cancel).await {
+            Either::Left((Ok(accepted), _)) => accepted,
+            Either::Left((Err(_), _)) => return,
+            Either::Right(_) => return,
+        };
+
+        if sender.is_closed() {
+            return;
+        }
+
+        match handle_notify(stream, &token).await {
+            Ok(Some((seq, vars))) => {
+                let is_duplicate = match (last_seq, seq) {
+                    (Some(last), seq) if seq != 0 && seq <= last => true,
+                    _ => false,
+                };
+                if is_duplicate {
+                    continue;
+                }
+                last_seq = Some(seq);
+
+                if sender.unbounded_send(Ok(vars)).is_err() {
+                    return;
+                }
+            }
+            Ok(None) => continue,
+            Err(e) => {
+                let _ = sender.unbounded_send(Err(e));
+            }
+        }
+    }
+}
+
+async fn handle_notify(mut stream: TcpStream, token: &str) -> Result<Option<(u64, StateVars)>> {
+    let (request_line, headers, body) = read_http_request(&mut stream).await?;
+
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+        .await?;
+
+    if !request_line.starts_with("NOTIFY") || !request_line.contains(token) {
+        return Ok(None);
+    }
+
+    let seq: u64 = headers.get("seq").and_then(|v| v.parse().ok()).unwrap_or(0);
+
+    Ok(Some((seq, parse_propertyset(&body)?)))
+}
+
+/// Reads a minimal HTTP/1.1 request (request line, headers, body) off `stream`.
+async fn read_http_request(
+    stream: &mut TcpStream,
+) -> Result<(String, HashMap<String, String>, String)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let headers_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break buf.len();
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = find_subslice(&buf, b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let header_text = String::from_utf8_lossy(&buf[..headers_end]).to_string();
+    let mut lines = header_text.split("\r\n");
+    let request_line = lines.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    let mut body = buf[headers_end..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    Ok((
+        request_line,
+        headers,
+        String::from_utf8_lossy(&body).to_string(),
+    ))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Parses a GENA `<e:propertyset>` NOTIFY body into a [`StateVars`] batch.
+fn parse_propertyset(xml: &str) -> Result<StateVars> {
+    let document = Document::parse(xml)?;
+    let root = find_root(&document, "propertyset", "propertyset")?;
+
+    let mut vars = StateVars::new();
+    for property in root.children().filter(|n| n.has_tag_name("property")) {
+        for variable in property.children().filter(roxmltree::Node::is_element) {
+            vars.insert(
+                variable.tag_name().name().to_string(),
+                variable.text().unwrap_or_default().to_string(),
+            );
+        }
+    }
+
+    Ok(vars)
+}
+
+/// Determines the local IP address that would be used to reach `target`, by connecting a
+/// UDP socket to it and inspecting the chosen local address. No packets are actually sent.
+fn local_ip_towards(target: &http::Uri) -> Result<std::net::IpAddr> {
+    let host = target
+        .host()
+        .ok_or_else(|| Error::invalid_response_msg("device url has no host"))?;
+    let port = target.port_u16().unwrap_or(80);
+
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect((host, port))?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// A short, unique-enough token to identify our callback path in `NOTIFY` requests, since
+/// several subscriptions may share the same local listener port across retries.
+fn fresh_token() -> String {
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now().hash(&mut hasher);
+    std::thread::current().id().hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
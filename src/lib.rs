@@ -10,6 +10,10 @@
 //! UPnP stand for `Universal Plug and Play` and is widely used for routers, WiFi-enabled speakers
 //! and media servers.
 //!
+//! Enable the `serde` feature to derive `Serialize`/`Deserialize` on [`DeviceSpec`] and
+//! [`Service`], so a discovered device tree can be dumped to JSON for caching or
+//! inspection without re-walking the XML.
+//!
 //! # Example usage:
 //! ```rust,no_run
 //! # async fn discovery() -> Result<(), upnp::Error> {
@@ -21,38 +25,45 @@
 //!
 //! let devices = upnp::discover(&RENDERING_CONTROL.into(), Duration::from_secs(3)).await?;
 //! pin_utils::pin_mut!(devices);
-//! 
+//!
 //! while let Some(device) = devices.next().await {
 //!     let device = device?;
-//! 
+//!
 //!     let service = device
 //!         .find_service(&RENDERING_CONTROL)
 //!         .expect("searched for RenderingControl, got something else");
-//! 
+//!
 //!     let args = "<InstanceID>0</InstanceID><Channel>Master</Channel>";
 //!     let response = service.action(device.url(), "GetVolume", args).await?;
-//! 
+//!
 //!     let volume = response.get("CurrentVolume").unwrap();
-//! 
+//!
 //!     println!("'{}' is at volume {}", device.friendly_name(), volume);
 //! }
-//! 
+//!
 //! # Ok(())
 //! # }
 //! ```
 // doc include when it gets stable
 
 mod device;
+/// DIDL-Lite metadata, as returned by e.g. `AVTransport`'s `CurrentURIMetaData` or
+/// `ContentDirectory::Browse`.
+pub mod didl;
 mod discovery;
 mod error;
+mod gena;
 
 /// Service Control Protocol Description.
 pub mod scpd;
+#[cfg(feature = "serde")]
+mod serde_support;
 mod service;
 
 pub use device::{Device, DeviceSpec};
-pub use discovery::discover;
+pub use discovery::{discover, discover_all, find_device_by_name};
 pub use error::Error;
+pub use gena::StateVars;
 pub use service::Service;
 
 pub use http;
@@ -0,0 +1,240 @@
+//! Service Control Protocol Description.
+//!
+//! Parses the XML document a service's `SCPDURL` points to, which declares the actions
+//! a service supports and the state variables those actions read and write.
+
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use crate::{find_in_xml, find_root, Error, Result};
+
+/// The direction of an action argument, relative to the control point calling the action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The caller supplies this argument.
+    In,
+    /// The device returns this argument in the response.
+    Out,
+}
+
+/// A single argument of an [`Action`].
+#[derive(Debug, Clone)]
+pub struct Argument {
+    name: String,
+    direction: Direction,
+    state_variable: String,
+}
+
+impl Argument {
+    /// The argument's name, as it appears in the SOAP request/response body.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether this is an in- or out-argument.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// The name of the [`StateVariable`] this argument is related to.
+    pub fn state_variable(&self) -> &str {
+        &self.state_variable
+    }
+}
+
+/// A single action a service supports, as declared in its SCPD.
+#[derive(Debug, Clone)]
+pub struct Action {
+    name: String,
+    arguments: Vec<Argument>,
+}
+
+impl Action {
+    /// The action's name, e.g. `GetVolume`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// All arguments of this action, both in- and out-arguments.
+    pub fn arguments(&self) -> &[Argument] {
+        &self.arguments
+    }
+
+    /// The in-arguments a caller is expected to supply.
+    pub fn in_arguments(&self) -> impl Iterator<Item = &Argument> {
+        self.arguments
+            .iter()
+            .filter(|arg| arg.direction == Direction::In)
+    }
+
+    /// The out-arguments the device returns in its response.
+    pub fn out_arguments(&self) -> impl Iterator<Item = &Argument> {
+        self.arguments
+            .iter()
+            .filter(|arg| arg.direction == Direction::Out)
+    }
+
+    fn from_xml(node: Node<'_, '_>) -> Result<Self> {
+        let (name, argument_list) = find_in_xml!(node => name, ?argumentList);
+        let name = name.text().unwrap_or_default().to_string();
+
+        let mut arguments = Vec::new();
+        if let Some(argument_list) = argument_list {
+            for argument in argument_list
+                .children()
+                .filter(|n| n.has_tag_name("argument"))
+            {
+                let (arg_name, direction, related) =
+                    find_in_xml!(argument => name, direction, relatedStateVariable);
+
+                let direction = match direction.text().unwrap_or_default() {
+                    "in" => Direction::In,
+                    "out" => Direction::Out,
+                    other => {
+                        return Err(Error::invalid_response_msg(format!(
+                            "unknown argument direction `{}`",
+                            other
+                        )))
+                    }
+                };
+
+                arguments.push(Argument {
+                    name: arg_name.text().unwrap_or_default().to_string(),
+                    direction,
+                    state_variable: related.text().unwrap_or_default().to_string(),
+                });
+            }
+        }
+
+        Ok(Action { name, arguments })
+    }
+}
+
+/// The declared data type and constraints of a state variable.
+#[derive(Debug, Clone)]
+pub struct StateVariable {
+    name: String,
+    data_type: String,
+    allowed_values: Option<Vec<String>>,
+}
+
+impl StateVariable {
+    /// The variable's name, e.g. `Volume`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The UPnP data type this variable was declared with, e.g. `ui4`, `boolean` or `string`.
+    pub fn data_type(&self) -> &str {
+        &self.data_type
+    }
+
+    /// The set of values this variable is allowed to take, if the SCPD declared an
+    /// `allowedValueList`.
+    pub fn allowed_values(&self) -> Option<&[String]> {
+        self.allowed_values.as_deref()
+    }
+
+    /// Whether `value` is a valid value for this state variable: it must parse as the
+    /// variable's declared data type, and, if an `allowedValueList` was declared, be one
+    /// of those values.
+    ///
+    /// Only integer, floating-point and boolean types are checked against their form; a
+    /// value of any other declared type (e.g. `string`) passes the type check.
+    pub fn validate(&self, value: &str) -> bool {
+        let type_ok = match self.data_type.as_str() {
+            "ui1" => value.parse::<u8>().is_ok(),
+            "ui2" => value.parse::<u16>().is_ok(),
+            "ui4" => value.parse::<u32>().is_ok(),
+            "ui8" => value.parse::<u64>().is_ok(),
+            "i1" => value.parse::<i8>().is_ok(),
+            "i2" => value.parse::<i16>().is_ok(),
+            "i4" => value.parse::<i32>().is_ok(),
+            "i8" | "int" => value.parse::<i64>().is_ok(),
+            "r4" | "r8" | "number" | "fixed.14.4" | "float" => value.parse::<f64>().is_ok(),
+            "boolean" => matches!(value, "0" | "1" | "true" | "false" | "yes" | "no"),
+            _ => true,
+        };
+
+        type_ok
+            && match &self.allowed_values {
+                Some(allowed) => allowed.iter().any(|v| v == value),
+                None => true,
+            }
+    }
+
+    fn from_xml(node: Node<'_, '_>) -> Result<Self> {
+        let (name, data_type, allowed_value_list) =
+            find_in_xml!(node => name, dataType, ?allowedValueList);
+
+        let allowed_values = allowed_value_list.map(|list| {
+            list.children()
+                .filter(|n| n.has_tag_name("allowedValue"))
+                .map(|n| n.text().unwrap_or_default().to_string())
+                .collect()
+        });
+
+        Ok(StateVariable {
+            name: name.text().unwrap_or_default().to_string(),
+            data_type: data_type.text().unwrap_or_default().to_string(),
+            allowed_values,
+        })
+    }
+}
+
+/// A parsed Service Control Protocol Description document.
+#[derive(Debug, Clone)]
+pub struct SCPD {
+    actions: HashMap<String, Action>,
+    state_variables: HashMap<String, StateVariable>,
+}
+
+impl SCPD {
+    pub(crate) fn from_xml(xml: &str) -> Result<Self> {
+        let document = Document::parse(xml)?;
+        let root = find_root(&document, "scpd", "scpd")?;
+        let (action_list, service_state_table) =
+            find_in_xml!(root => actionList, serviceStateTable);
+
+        let mut actions = HashMap::new();
+        for action in action_list.children().filter(|n| n.has_tag_name("action")) {
+            let action = Action::from_xml(action)?;
+            actions.insert(action.name.clone(), action);
+        }
+
+        let mut state_variables = HashMap::new();
+        for state_variable in service_state_table
+            .children()
+            .filter(|n| n.has_tag_name("stateVariable"))
+        {
+            let state_variable = StateVariable::from_xml(state_variable)?;
+            state_variables.insert(state_variable.name.clone(), state_variable);
+        }
+
+        Ok(SCPD {
+            actions,
+            state_variables,
+        })
+    }
+
+    /// Look up an action by name.
+    pub fn action(&self, name: &str) -> Option<&Action> {
+        self.actions.get(name)
+    }
+
+    /// All actions declared by this service.
+    pub fn actions(&self) -> impl Iterator<Item = &Action> {
+        self.actions.values()
+    }
+
+    /// Look up a state variable by name.
+    pub fn state_variable(&self, name: &str) -> Option<&StateVariable> {
+        self.state_variables.get(name)
+    }
+
+    /// All state variables declared by this service.
+    pub fn state_variables(&self) -> impl Iterator<Item = &StateVariable> {
+        self.state_variables.values()
+    }
+}
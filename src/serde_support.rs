@@ -0,0 +1,27 @@
+//! Serde support for fields whose type doesn't implement `Serialize`/`Deserialize`
+//! natively, but does implement `Display`/`FromStr` — namely `http::Uri` and
+//! `ssdp_client::URN`. Used via `#[serde(with = "crate::serde_support")]`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub(crate) fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Display,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::Stream;
+use once_cell::sync::OnceCell;
+use roxmltree::{Document, Node};
+use ssdp_client::URN;
+
+use crate::{
+    find_in_xml, find_root,
+    gena::{self, StateVars},
+    scpd::SCPD,
+    Error, HttpResponseExt, Result,
+};
+
+/// A single service exposed by a [`Device`](crate::Device), e.g. `RenderingControl` or
+/// `AVTransport`.
+///
+/// Obtained through [`DeviceSpec::services`](crate::DeviceSpec::services) or
+/// [`DeviceSpec::find_service`](crate::DeviceSpec::find_service).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Service {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support"))]
+    service_type: URN,
+    service_id: String,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support"))]
+    scpd_endpoint: http::Uri,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support"))]
+    control_endpoint: http::Uri,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support"))]
+    event_sub_endpoint: http::Uri,
+    /// Lazily fetched and cached by [`Service::action_checked`]; deliberately not carried
+    /// across serialization, since a deserialized `Service` hasn't actually fetched it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scpd_cache: OnceCell<SCPD>,
+}
+
+impl Service {
+    pub(crate) fn from_xml(device_url: &http::Uri, node: Node<'_, '_>) -> Result<Self> {
+        let (service_type, service_id, scpd_url, control_url, event_sub_url) = find_in_xml!(
+            node => serviceType, serviceId, SCPDURL, controlURL, eventSubURL
+        );
+
+        let service_type = service_type
+            .text()
+            .unwrap_or_default()
+            .parse()
+            .map_err(Error::invalid_response)?;
+
+        Ok(Service {
+            service_type,
+            service_id: service_id.text().unwrap_or_default().to_string(),
+            scpd_endpoint: resolve_url(device_url, scpd_url.text().unwrap_or_default())?,
+            control_endpoint: resolve_url(device_url, control_url.text().unwrap_or_default())?,
+            event_sub_endpoint: resolve_url(device_url, event_sub_url.text().unwrap_or_default())?,
+            scpd_cache: OnceCell::new(),
+        })
+    }
+
+    /// The URN identifying this service's type, e.g.
+    /// `urn:schemas-upnp-org:service:RenderingControl:1`.
+    pub fn service_type(&self) -> &URN {
+        &self.service_type
+    }
+
+    /// The service's `serviceId`, e.g. `urn:upnp-org:serviceId:RenderingControl`.
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    /// The absolute URL this service's SCPD can be fetched from.
+    pub fn scpd_endpoint(&self) -> &http::Uri {
+        &self.scpd_endpoint
+    }
+
+    /// The absolute URL SOAP actions are posted to.
+    pub fn control_endpoint(&self) -> &http::Uri {
+        &self.control_endpoint
+    }
+
+    /// The absolute URL GENA subscriptions are sent to.
+    pub fn event_sub_endpoint(&self) -> &http::Uri {
+        &self.event_sub_endpoint
+    }
+
+    /// Fetch and parse this service's SCPD.
+    pub async fn scpd(&self) -> Result<SCPD> {
+        let body = isahc::get_async(&self.scpd_endpoint)
+            .await?
+            .err_if_not_200()?
+            .text()
+            .await?;
+
+        SCPD::from_xml(&body)
+    }
+
+    /// The parsed SCPD, fetched once and cached for the lifetime of this `Service`.
+    ///
+    /// Used by [`Service::action_checked`] so that repeated calls (e.g. polling an action
+    /// on an interval) don't pay a SCPD fetch-and-parse round trip every time.
+    async fn scpd_cached(&self) -> Result<&SCPD> {
+        if let Some(scpd) = self.scpd_cache.get() {
+            return Ok(scpd);
+        }
+
+        let scpd = self.scpd().await?;
+        Ok(self.scpd_cache.get_or_init(|| scpd))
+    }
+
+    /// Call an action on the device, with a raw, pre-built XML argument string.
+    ///
+    /// `url` is the device's root URL; it is not used. The request is sent to
+    /// [`Service::control_endpoint`], with a SOAPACTION header built from the service type
+    /// and `action`.
+    pub async fn action(
+        &self,
+        url: &http::Uri,
+        action: &str,
+        payload: &str,
+    ) -> Result<HashMap<String, String>> {
+        let body = self.soap_body(action, payload);
+        let response = self.post_soap(url, action, &body).await?;
+        parse_action_response(action, &response)
+    }
+
+    async fn post_soap(&self, _url: &http::Uri, action: &str, body: &str) -> Result<String> {
+        let soap_action = format!("\"{}#{}\"", self.service_type, action);
+
+        let request = isahc::Request::post(&self.control_endpoint)
+            .header("Content-Type", "text/xml; charset=\"utf-8\"")
+            .header("SOAPACTION", soap_action)
+            .body(body.to_string())
+            .map_err(Error::invalid_response)?;
+
+        isahc::send_async(request)
+            .await?
+            .err_if_not_200()?
+            .text()
+            .await
+            .map_err(Error::from)
+    }
+
+    fn soap_body(&self, action: &str, payload: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">
+  <s:Body>
+    <u:{action} xmlns:u="{service_type}">
+      {payload}
+    </u:{action}>
+  </s:Body>
+</s:Envelope>"#,
+            action = action,
+            service_type = self.service_type,
+            payload = payload,
+        )
+    }
+
+    /// Call an action on the device, validating `args` against the service's SCPD first.
+    /// Values that pass are sent as given (XML-escaped); they are not converted to another
+    /// form.
+    ///
+    /// Unlike [`Service::action`], this looks the action up in the SCPD fetched from
+    /// [`Service::scpd_endpoint`] (fetched once and cached for the lifetime of this
+    /// `Service`), rejects unknown action or argument names, checks that every declared
+    /// in-argument was supplied, validates each value against its state variable's
+    /// declared data type and `allowedValueList`, and maps the response back to the
+    /// action's declared out-arguments by name.
+    pub async fn action_checked(
+        &self,
+        url: &http::Uri,
+        name: &str,
+        args: &[(&str, &str)],
+    ) -> Result<HashMap<String, String>> {
+        let scpd = self.scpd_cached().await?;
+        let action = scpd
+            .action(name)
+            .ok_or_else(|| Error::ActionNotFound(name.to_string()))?;
+
+        let mut payload = String::new();
+        for arg in action.in_arguments() {
+            let value = args
+                .iter()
+                .find(|(arg_name, _)| *arg_name == arg.name())
+                .map(|(_, value)| *value)
+                .ok_or_else(|| Error::MissingArgument(name.to_string(), arg.name().to_string()))?;
+
+            if let Some(state_variable) = scpd.state_variable(arg.state_variable()) {
+                if !state_variable.validate(value) {
+                    return Err(Error::InvalidArgument(
+                        arg.name().to_string(),
+                        format!("`{}` is not a valid {}", value, state_variable.data_type()),
+                    ));
+                }
+            }
+
+            payload.push_str(&format!(
+                "<{0}>{1}</{0}>",
+                arg.name(),
+                escape_xml_text(value)
+            ));
+        }
+
+        for (arg_name, _) in args {
+            if !action.in_arguments().any(|arg| arg.name() == *arg_name) {
+                return Err(Error::ArgumentNotFound(
+                    name.to_string(),
+                    arg_name.to_string(),
+                ));
+            }
+        }
+
+        let response = self.action(url, name, &payload).await?;
+
+        Ok(action
+            .out_arguments()
+            .filter_map(|arg| {
+                response
+                    .get(arg.name())
+                    .map(|value| (arg.name().to_string(), value.clone()))
+            })
+            .collect())
+    }
+
+    /// Subscribe to this service's GENA event notifications.
+    ///
+    /// Issues a `SUBSCRIBE` request to [`Service::event_sub_endpoint`] and starts a small
+    /// local HTTP listener to receive the device's `NOTIFY` callbacks. The subscription is
+    /// renewed automatically before the timeout the device granted elapses (`timeout` is only
+    /// what is requested), and `UNSUBSCRIBE` is sent once the
+    /// returned stream is dropped.
+    pub async fn subscribe(
+        &self,
+        device_url: &http::Uri,
+        timeout: Duration,
+    ) -> Result<impl Stream<Item = Result<StateVars>>> {
+        gena::subscribe(self, device_url, timeout).await
+    }
+}
+
+fn resolve_url(device_url: &http::Uri, path: &str) -> Result<http::Uri> {
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return path.parse().map_err(Error::from);
+    }
+
+    let authority = device_url
+        .authority()
+        .ok_or_else(|| Error::invalid_response_msg("device url has no authority"))?;
+
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    };
+
+    format!(
+        "{}://{}{}",
+        device_url.scheme_str().unwrap_or("http"),
+        authority,
+        path
+    )
+    .parse()
+    .map_err(Error::from)
+}
+
+fn escape_xml_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn parse_action_response(action: &str, xml: &str) -> Result<HashMap<String, String>> {
+    let document = Document::parse(xml)?;
+    let response_name = format!("{}Response", action);
+    let root = find_root(&document, &response_name, "Envelope")?;
+
+    Ok(root
+        .children()
+        .filter(Node::is_element)
+        .map(|node| {
+            (
+                node.tag_name().name().to_string(),
+                node.text().unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}